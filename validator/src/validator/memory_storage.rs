@@ -0,0 +1,190 @@
+use std::fs::OpenOptions;
+use std::ops::{Index, IndexMut, Range};
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use memmap2::{MmapMut, MmapOptions};
+
+/// Minimum size of a backing file: a shorter file is extended to this length
+/// when opened. The mapping is made once at open and is never grown or
+/// recreated afterwards.
+const INITIAL_CAPACITY: u64 = 1 << 20;
+
+/// Shared mmap-backed byte storage behind both `MemoryMappedFile` and
+/// `MemoryMappedStorage` — they only differ in the constructor name each
+/// call site expects.
+struct MappedBytes {
+    mmap: MmapMut,
+}
+
+impl MappedBytes {
+    fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).create(true).open(path)?;
+
+        let len = file.metadata()?.len().max(INITIAL_CAPACITY);
+        file.set_len(len)?;
+
+        let mmap = unsafe { MmapOptions::new().map_mut(&file)? };
+
+        Ok(Self { mmap })
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.mmap.flush()?;
+        Ok(())
+    }
+
+    /// Blake3 digest over the populated prefix `[0, len)`.
+    fn digest(&self, len: usize) -> [u8; 32] {
+        let len = len.min(self.mmap.len());
+        *blake3::hash(&self.mmap[..len]).as_bytes()
+    }
+
+    /// Recomputes the digest over `[0, len)` and compares it against
+    /// `expected`, refusing to continue on mismatch rather than silently
+    /// running on top of corrupted data.
+    fn verify_integrity(&self, len: usize, expected: [u8; 32]) -> Result<()> {
+        let actual = self.digest(len);
+
+        if actual != expected {
+            bail!(
+                "integrity check failed: expected digest {}, found {}",
+                hex(&expected),
+                hex(&actual),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl Index<usize> for MappedBytes {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &u8 {
+        &self.mmap[index]
+    }
+}
+
+impl IndexMut<usize> for MappedBytes {
+    fn index_mut(&mut self, index: usize) -> &mut u8 {
+        &mut self.mmap[index]
+    }
+}
+
+impl Index<Range<usize>> for MappedBytes {
+    type Output = [u8];
+
+    fn index(&self, range: Range<usize>) -> &[u8] {
+        &self.mmap[range]
+    }
+}
+
+impl IndexMut<Range<usize>> for MappedBytes {
+    fn index_mut(&mut self, range: Range<usize>) -> &mut [u8] {
+        &mut self.mmap[range]
+    }
+}
+
+fn hex(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// A fixed memory-mapped file, used for `center_column.bin`.
+pub struct MemoryMappedFile(MappedBytes);
+
+impl MemoryMappedFile {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self(MappedBytes::open(path)?))
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.0.flush()
+    }
+
+    pub fn digest(&self, len: usize) -> [u8; 32] {
+        self.0.digest(len)
+    }
+
+    pub fn verify_integrity(&self, len: usize, expected: [u8; 32]) -> Result<()> {
+        self.0.verify_integrity(len, expected)
+    }
+}
+
+impl Index<usize> for MemoryMappedFile {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &u8 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for MemoryMappedFile {
+    fn index_mut(&mut self, index: usize) -> &mut u8 {
+        &mut self.0[index]
+    }
+}
+
+impl Index<Range<usize>> for MemoryMappedFile {
+    type Output = [u8];
+
+    fn index(&self, range: Range<usize>) -> &[u8] {
+        &self.0[range]
+    }
+}
+
+impl IndexMut<Range<usize>> for MemoryMappedFile {
+    fn index_mut(&mut self, range: Range<usize>) -> &mut [u8] {
+        &mut self.0[range]
+    }
+}
+
+/// The memory-mapped backing store for `current_row.bin`, the evolving
+/// Rule 30 row.
+pub struct MemoryMappedStorage(MappedBytes);
+
+impl MemoryMappedStorage {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self(MappedBytes::open(path)?))
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.0.flush()
+    }
+
+    pub fn digest(&self, len: usize) -> [u8; 32] {
+        self.0.digest(len)
+    }
+
+    pub fn verify_integrity(&self, len: usize, expected: [u8; 32]) -> Result<()> {
+        self.0.verify_integrity(len, expected)
+    }
+}
+
+impl Index<usize> for MemoryMappedStorage {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &u8 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for MemoryMappedStorage {
+    fn index_mut(&mut self, index: usize) -> &mut u8 {
+        &mut self.0[index]
+    }
+}
+
+impl Index<Range<usize>> for MemoryMappedStorage {
+    type Output = [u8];
+
+    fn index(&self, range: Range<usize>) -> &[u8] {
+        &self.0[range]
+    }
+}
+
+impl IndexMut<Range<usize>> for MemoryMappedStorage {
+    fn index_mut(&mut self, range: Range<usize>) -> &mut [u8] {
+        &mut self.0[range]
+    }
+}
@@ -0,0 +1,136 @@
+use std::cmp::min;
+
+use blake3::Hasher;
+
+/// Size in bytes of a single leaf of the row Merkle tree.
+pub const LEAF_SIZE: usize = 64;
+
+const LEAF_DOMAIN: u8 = 0x00;
+const NODE_DOMAIN: u8 = 0x01;
+
+/// A binary Merkle tree over the evolution row. Leaves are fixed-size
+/// `LEAF_SIZE` chunks; odd-sized levels duplicate their last node, matching
+/// `verify`/`prove` below.
+///
+/// `current_row` is round-tripped through miners in full on every step (the
+/// whole row is re-evolved in place, not just a newly-grown tail), so its
+/// bytes can change even on a step where its length doesn't. `update`
+/// therefore re-hashes every leaf from the row each call rather than only
+/// the ones a length check would flag as new; `rebuild_spine` then just
+/// recombines those hashes, so the cost tracks the (small) leaf count, not
+/// the row's byte size.
+#[derive(Debug, Default, Clone)]
+pub struct MerkleTree {
+    levels: Vec<Vec<[u8; 32]>>,
+    leaf_count: usize,
+}
+
+impl MerkleTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or([0u8; 32])
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Rehashes every leaf covering `row` and rebuilds the levels above them.
+    pub fn update(&mut self, row: &[u8]) {
+        let target_leaf_count = (row.len() + LEAF_SIZE - 1) / LEAF_SIZE;
+
+        let leaves = (0..target_leaf_count)
+            .map(|leaf_index| {
+                let start = leaf_index * LEAF_SIZE;
+                let end = min(start + LEAF_SIZE, row.len());
+                hash_leaf(&row[start..end])
+            })
+            .collect();
+
+        self.leaf_count = target_leaf_count;
+        self.levels = vec![leaves];
+
+        self.rebuild_spine();
+    }
+
+    /// Rebuilds every parent level from `levels[0]`'s leaf hashes. This
+    /// only recombines already-computed 32-byte hashes, so its cost tracks
+    /// the (small) number of leaves, not the size of the underlying row.
+    fn rebuild_spine(&mut self) {
+        self.levels.truncate(1);
+        let mut level = 0;
+
+        while self.levels[level].len() > 1 {
+            let parents = self.levels[level]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_pair(left, right),
+                    [left] => hash_pair(left, left),
+                    _ => unreachable!(),
+                })
+                .collect();
+
+            self.levels.push(parents);
+            level += 1;
+        }
+    }
+
+    /// Returns the sibling path from `leaf_index` up to the root, or
+    /// `None` if the leaf hasn't been committed yet.
+    pub fn prove(&self, leaf_index: usize) -> Option<Vec<[u8; 32]>> {
+        if leaf_index >= self.leaf_count {
+            return None;
+        }
+
+        let mut proof = Vec::with_capacity(self.levels.len().saturating_sub(1));
+        let mut index = leaf_index;
+
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling_index = index ^ 1;
+            let sibling = level.get(sibling_index).copied().unwrap_or(level[index]);
+            proof.push(sibling);
+            index /= 2;
+        }
+
+        Some(proof)
+    }
+}
+
+/// Verifies that `leaf_bytes` is the leaf at `leaf_index` under `root`,
+/// given the sibling path returned by `MerkleTree::prove`.
+pub fn verify(root: [u8; 32], mut leaf_index: usize, leaf_bytes: &[u8], proof: &[[u8; 32]]) -> bool {
+    let mut hash = hash_leaf(leaf_bytes);
+
+    for sibling in proof {
+        hash = if leaf_index % 2 == 0 {
+            hash_pair(&hash, sibling)
+        } else {
+            hash_pair(sibling, &hash)
+        };
+        leaf_index /= 2;
+    }
+
+    hash == root
+}
+
+fn hash_leaf(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Hasher::new();
+    hasher.update(&[LEAF_DOMAIN]);
+    hasher.update(bytes);
+    *hasher.finalize().as_bytes()
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Hasher::new();
+    hasher.update(&[NODE_DOMAIN]);
+    hasher.update(left);
+    hasher.update(right);
+    *hasher.finalize().as_bytes()
+}
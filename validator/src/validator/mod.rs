@@ -1,12 +1,15 @@
 use std::cell::UnsafeCell;
 use std::cmp::min;
+use std::collections::HashSet;
 use std::fs;
+use std::io;
 use std::io::{Read, Write};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
 use std::path::{Path, PathBuf};
 use std::simd::Simd;
-use std::sync::Arc;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
 
 use anyhow::Result;
 use dirs;
@@ -17,11 +20,38 @@ use tracing::{error, info};
 use neuron::{AccountId, config, hotkey_location, Keypair, load_key_seed, NeuronInfoLite, Subtensor};
 
 use crate::validator::memory_storage::{MemoryMappedFile, MemoryMappedStorage};
+use crate::validator::merkle::MerkleTree;
 
 mod memory_storage;
+mod merkle;
+mod scoring;
 
 const VERSION_KEY: u64 = 1;
 
+/// Read/write/connect timeout for miner axons, so a hung miner can't block
+/// the thread pool's `join()` indefinitely.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many times a failed range is redistributed to another miner before
+/// the validator gives up on it for this step.
+const MAX_RETRY_ROUNDS: usize = 3;
+
+/// A connected miner, still tied to its hotkey so scoring can attribute
+/// results after its `TcpStream` has been moved into a worker thread.
+struct MinerConnection {
+    hotkey: AccountId,
+    stream: TcpStream,
+}
+
+/// A completed chunk exchange reported back from the thread pool: either
+/// the primary copy (already written into `current_row`) or a redundant
+/// copy kept only so its bytes can be spot-checked against the locally
+/// recomputed transition.
+enum ScoringSample {
+    Primary { hotkey: AccountId, range: Range<usize>, result: io::Result<()> },
+    Redundant { hotkey: AccountId, range: Range<usize>, result: io::Result<Vec<u8>> },
+}
+
 #[derive(Clone)]
 struct CurrentRow(Arc<UnsafeCell<MemoryMappedStorage>>);
 
@@ -52,6 +82,25 @@ struct ValidatorState {
     step: u64,
     hotkeys: Vec<AccountId>,
     scores: Vec<u16>,
+    /// Merkle root of `current_row` as it stood at the end of each completed step,
+    /// indexed by `step - 1`, so a past row can be audited without re-downloading it.
+    #[serde(default)]
+    row_roots: Vec<[u8; 32]>,
+
+    /// Populated length of `current_row.bin`/`center_column.bin` and a digest over
+    /// that prefix, refreshed on every `save_state`, so a restored snapshot can
+    /// prove its backing files weren't silently corrupted before resuming.
+    /// `None` until the first `save_state` call that recorded one, so an
+    /// older state.json predating this feature doesn't fail a check it never
+    /// promised to pass.
+    #[serde(default)]
+    row_len: usize,
+    #[serde(default)]
+    row_digest: Option<[u8; 32]>,
+    #[serde(default)]
+    center_column_len: usize,
+    #[serde(default)]
+    center_column_digest: Option<[u8; 32]>,
 }
 
 pub struct Validator {
@@ -63,6 +112,7 @@ pub struct Validator {
     current_row: CurrentRow,
     center_column: MemoryMappedFile,
     state: ValidatorState,
+    row_merkle: MerkleTree,
 
     last_metagraph_sync: u64,
 
@@ -110,6 +160,7 @@ impl Validator {
             step: 1,
             scores,
             hotkeys,
+            ..Default::default()
         };
 
         let current_row = CurrentRow::new("current_row.bin").unwrap();
@@ -123,6 +174,7 @@ impl Validator {
             current_row,
             center_column,
             state,
+            row_merkle: MerkleTree::new(),
             last_metagraph_sync,
             thread_pool: ThreadPool::new(256),
         };
@@ -133,6 +185,22 @@ impl Validator {
             // Initial state
             validator.current_row[0] = 1;
             validator.center_column[0] = 1;
+            // Only byte 0 of center_column is ever written today, so that's
+            // the only prefix the integrity digest can honestly cover.
+            validator.state.center_column_len = 1;
+        } else {
+            if let Some(expected) = validator.state.row_digest {
+                validator
+                    .current_row
+                    .verify_integrity(validator.state.row_len, expected)
+                    .expect("current_row.bin failed integrity check on resume");
+            }
+            if let Some(expected) = validator.state.center_column_digest {
+                validator
+                    .center_column
+                    .verify_integrity(validator.state.center_column_len, expected)
+                    .expect("center_column.bin failed integrity check on resume");
+            }
         }
 
         validator
@@ -152,12 +220,15 @@ impl Validator {
         dir
     }
 
-    fn save_state(&self) -> Result<()> {
+    fn save_state(&mut self) -> Result<()> {
         let path = self.state_path();
 
         self.center_column.flush()?;
         self.current_row.flush()?;
 
+        self.state.row_digest = Some(self.current_row.digest(self.state.row_len));
+        self.state.center_column_digest = Some(self.center_column.digest(self.state.center_column_len));
+
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
@@ -230,19 +301,63 @@ impl Validator {
         Ok(())
     }
 
-    fn handle_connection(mut current_row: CurrentRow, mut connection: TcpStream, start: usize, end: usize) {
+    fn neuron_address(neuron: &NeuronInfoLite) -> SocketAddr {
+        let ip: IpAddr = if neuron.axon_info.ip_type == 4 {
+            Ipv4Addr::from(neuron.axon_info.ip as u32).into()
+        } else {
+            Ipv6Addr::from(neuron.axon_info.ip).into()
+        };
+
+        SocketAddr::new(ip, neuron.axon_info.port)
+    }
+
+    /// Connects to a miner's axon with a bounded connect/read/write timeout,
+    /// so a hung miner can only ever stall the one worker thread it's on.
+    fn connect(address: SocketAddr) -> io::Result<TcpStream> {
+        let stream = TcpStream::connect_timeout(&address, CONNECTION_TIMEOUT)?;
+        stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+        stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+
+        Ok(stream)
+    }
+
+    fn handle_connection(mut current_row: CurrentRow, mut connection: TcpStream, start: usize, end: usize) -> io::Result<()> {
+        if start >= end {
+            return Ok(());
+        }
+
         let buffer_size = min(end - start, 8 * 4 * 512);
 
-        let iterations = (end - start) / buffer_size;
+        for chunk_start in (start..end).step_by(buffer_size) {
+            let chunk_end = min(chunk_start + buffer_size, end);
 
-        for i in 0..iterations {
-            let from = start + i * buffer_size;
-            let to = start + (i + 1) * buffer_size;
+            connection.write_all(&current_row[chunk_start..chunk_end])?;
+            connection.read_exact(&mut current_row[chunk_start..chunk_end])?;
+        }
 
-            // TODO error handle
-            connection.write(&current_row[from..to]).unwrap();
-            connection.read(&mut current_row[from..to]).unwrap();
+        Ok(())
+    }
+
+    /// Same exchange as `handle_connection`, but against an owned scratch
+    /// buffer instead of `current_row` — used for the redundant copy of a
+    /// chunk, which exists only to be spot-checked and scored.
+    fn fetch_redundant(mut connection: TcpStream, mut buffer: Vec<u8>) -> io::Result<Vec<u8>> {
+        if buffer.is_empty() {
+            return Ok(buffer);
         }
+
+        let buffer_size = min(buffer.len(), 8 * 4 * 512);
+        let len = buffer.len();
+
+        for chunk_start in (0..len).step_by(buffer_size) {
+            let chunk_end = min(chunk_start + buffer_size, len);
+            let chunk = &mut buffer[chunk_start..chunk_end];
+
+            connection.write_all(chunk)?;
+            connection.read_exact(chunk)?;
+        }
+
+        Ok(buffer)
     }
 
     async fn do_step(&mut self) -> Result<()> {
@@ -258,36 +373,216 @@ impl Validator {
         let mut connections = Vec::with_capacity(256);
 
         for neuron in &self.neurons {
-            let ip: IpAddr = if neuron.axon_info.ip_type == 4 {
-                Ipv4Addr::from(neuron.axon_info.ip as u32).into()
-            } else {
-                Ipv6Addr::from(neuron.axon_info.ip).into()
-            };
-
-            let address = SocketAddr::new(ip, neuron.axon_info.port);
+            let address = Self::neuron_address(neuron);
 
-            if let Ok(stream) = TcpStream::connect(address) {
-                connections.push(stream);
+            if let Ok(stream) = Self::connect(address) {
+                connections.push(MinerConnection { hotkey: neuron.hotkey.clone(), stream });
             }
         }
 
-        let connection_count = connections.len();
         let byte_count = (self.state.step / 4 + 1) as usize;
+        let previous_row_snapshot = self.current_row[0..byte_count].to_vec();
+
+        let assignments = scoring::assign_chunks(connections.len(), byte_count);
+        let mut connections: Vec<Option<MinerConnection>> = connections.into_iter().map(Some).collect();
+
+        let (result_tx, result_rx) = mpsc::channel();
+
+        for assignment in &assignments {
+            for (position, &owner) in assignment.owners.iter().enumerate() {
+                let MinerConnection { hotkey, stream } = connections[owner]
+                    .take()
+                    .expect("assign_chunks gives each connection at most one role per step");
+                let range = assignment.range.clone();
+                let tx = result_tx.clone();
+
+                if position == 0 {
+                    let row = self.current_row.clone();
+
+                    self.thread_pool.execute(move || {
+                        let result = Self::handle_connection(row, stream, range.start, range.end);
+                        let _ = tx.send(ScoringSample::Primary { hotkey, range, result });
+                    });
+                } else {
+                    let expected = previous_row_snapshot[range.clone()].to_vec();
+
+                    self.thread_pool.execute(move || {
+                        let result = Self::fetch_redundant(stream, expected);
+                        let _ = tx.send(ScoringSample::Redundant { hotkey, range, result });
+                    });
+                }
+            }
+        }
 
-        let chunk_size = if connection_count % 2 == 0 {
-            byte_count / connection_count + 1
-        } else {
-            byte_count / connection_count
-        };
+        drop(result_tx);
+        self.thread_pool.join();
 
-        // TODO Handle connection prematurely dying or giving invalid results
-        for (index, connection) in connections.into_iter().enumerate() {
-            let row = self.current_row.clone();
+        let mut primary_hotkeys = Vec::new();
+        let mut redundant_samples = Vec::new();
+        let mut unresolved_ranges = Vec::new();
+        let mut failed_hotkeys = HashSet::new();
+
+        for sample in result_rx.try_iter() {
+            match sample {
+                ScoringSample::Primary { hotkey, range, result } => match result {
+                    Ok(()) => primary_hotkeys.push((range, hotkey)),
+                    Err(error) => {
+                        error!("Miner {hotkey} failed on range {range:?}: {error}");
+                        scoring::penalize(&mut self.state.scores, &self.state.hotkeys, &hotkey);
+                        failed_hotkeys.insert(hotkey);
+                        unresolved_ranges.push(range);
+                    }
+                },
+                ScoringSample::Redundant { hotkey, range, result } => match result {
+                    Ok(bytes) => redundant_samples.push((range, hotkey, bytes)),
+                    Err(error) => {
+                        error!("Miner {hotkey} failed to return redundant copy of range {range:?}: {error}");
+                        scoring::penalize(&mut self.state.scores, &self.state.hotkeys, &hotkey);
+                        failed_hotkeys.insert(hotkey);
+                    }
+                },
+            }
+        }
 
-            self.thread_pool.execute(move || Self::handle_connection(row, connection, index * chunk_size, (index + 1) * chunk_size));
+        // Redistribute any range whose primary miner died or returned nothing
+        // to another, not-yet-failed miner, until the row is fully covered or
+        // we run out of retry budget.
+        let mut retry_round = 0;
+
+        while !unresolved_ranges.is_empty() && retry_round < MAX_RETRY_ROUNDS {
+            retry_round += 1;
+
+            let candidates: Vec<&NeuronInfoLite> = self
+                .neurons
+                .iter()
+                .filter(|neuron| !failed_hotkeys.contains(&neuron.hotkey))
+                .collect();
+
+            if candidates.is_empty() {
+                break;
+            }
+
+            let retry_count = min(unresolved_ranges.len(), candidates.len());
+            let (retry_tx, retry_rx) = mpsc::channel();
+            let mut next_unresolved = Vec::new();
+
+            for (range, neuron) in unresolved_ranges.drain(..retry_count).zip(candidates) {
+                let hotkey = neuron.hotkey.clone();
+
+                let stream = match Self::connect(Self::neuron_address(neuron)) {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        error!("Failed to reconnect to miner {hotkey} to retry range {range:?}: {error}");
+                        scoring::penalize(&mut self.state.scores, &self.state.hotkeys, &hotkey);
+                        failed_hotkeys.insert(hotkey);
+                        next_unresolved.push(range);
+                        continue;
+                    }
+                };
+
+                let row = self.current_row.clone();
+                let tx = retry_tx.clone();
+
+                self.thread_pool.execute(move || {
+                    let result = Self::handle_connection(row, stream, range.start, range.end);
+                    let _ = tx.send((hotkey, range, result));
+                });
+            }
+
+            drop(retry_tx);
+            self.thread_pool.join();
+
+            for (hotkey, range, result) in retry_rx.try_iter() {
+                match result {
+                    Ok(()) => primary_hotkeys.push((range, hotkey)),
+                    Err(error) => {
+                        error!("Miner {hotkey} failed retry of range {range:?}: {error}");
+                        scoring::penalize(&mut self.state.scores, &self.state.hotkeys, &hotkey);
+                        failed_hotkeys.insert(hotkey);
+                        next_unresolved.push(range);
+                    }
+                }
+            }
+
+            unresolved_ranges.extend(next_unresolved);
         }
 
-        self.thread_pool.join();
+        if !unresolved_ranges.is_empty() {
+            error!(
+                "Step {} left {} range(s) uncovered after exhausting retries: {:?}",
+                self.state.step,
+                unresolved_ranges.len(),
+                unresolved_ranges
+            );
+        }
+
+        for (range, redundant_hotkey, redundant_bytes) in redundant_samples {
+            let Some((_, primary_hotkey)) = primary_hotkeys.iter().find(|(primary_range, _)| *primary_range == range) else {
+                continue;
+            };
+
+            // Seeding on (step, range start) keeps the sample reproducible if this step's
+            // row ever needs to be re-audited later. Boundary cells are excluded since a
+            // miner only ever receives its own chunk and can't evolve those correctly.
+            let sample_indices = scoring::sample_interior_cell_indices(
+                self.state.step ^ range.start as u64,
+                range.len() * 8,
+                scoring::SPOT_CHECK_SAMPLES,
+            );
+
+            let mut primary_matches = 0usize;
+            let mut redundant_matches = 0usize;
+
+            for &local_bit in &sample_indices {
+                let expected = scoring::expected_bit(&previous_row_snapshot, range.start * 8 + local_bit);
+                let primary_bit = scoring::actual_bit(&self.current_row[range.clone()], local_bit);
+                let redundant_bit = scoring::actual_bit(&redundant_bytes, local_bit);
+
+                primary_matches += (primary_bit == expected) as usize;
+                redundant_matches += (redundant_bit == expected) as usize;
+            }
+
+            let sample_count = sample_indices.len().max(1) as f64;
+
+            scoring::record(
+                &mut self.state.scores,
+                &self.state.hotkeys,
+                primary_hotkey,
+                primary_matches as f64 / sample_count,
+            );
+            scoring::record(
+                &mut self.state.scores,
+                &self.state.hotkeys,
+                &redundant_hotkey,
+                redundant_matches as f64 / sample_count,
+            );
+        }
+
+        self.row_merkle.update(&self.current_row[0..byte_count]);
+        let root = self.row_merkle.root();
+        self.state.row_roots.push(root);
+
+        // Spot-check one committed leaf against the root we just computed —
+        // the same proof-based check an auditor could run against a past
+        // `row_roots` entry without re-downloading the whole row.
+        if self.row_merkle.leaf_count() > 0 {
+            let leaf_index = self.state.step as usize % self.row_merkle.leaf_count();
+            let leaf_start = leaf_index * merkle::LEAF_SIZE;
+            let leaf_end = min(leaf_start + merkle::LEAF_SIZE, byte_count);
+
+            if let Some(proof) = self.row_merkle.prove(leaf_index) {
+                let leaf_bytes = &self.current_row[leaf_start..leaf_end];
+
+                if !merkle::verify(root, leaf_index, leaf_bytes, &proof) {
+                    error!(
+                        "Merkle self-check failed for leaf {leaf_index} of step {}'s row",
+                        self.state.step
+                    );
+                }
+            }
+        }
+
+        self.state.row_len = byte_count;
 
         self.state.step += 1;
         self.save_state()?;
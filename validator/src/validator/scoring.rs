@@ -0,0 +1,189 @@
+use std::ops::Range;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use neuron::AccountId;
+
+/// How many distinct miners are assigned to each byte range; each one's
+/// result is spot-checked against the locally recomputed transition.
+pub const REDUNDANCY: usize = 2;
+
+/// How many cell indices to spot-check per chunk, per step.
+pub const SPOT_CHECK_SAMPLES: usize = 32;
+
+/// Smoothing factor for the per-hotkey score EMA; higher weighs recent
+/// steps more heavily.
+pub const EMA_ALPHA: f64 = 0.1;
+
+/// A byte range of the row and the connection indices responsible for it,
+/// `owners[0]` being the primary (whose result is written into the row)
+/// and the rest redundant copies kept only for comparison.
+pub struct ChunkAssignment {
+    pub range: Range<usize>,
+    pub owners: Vec<usize>,
+}
+
+/// Splits `[0, byte_count)` into at most `connection_count / REDUNDANCY`
+/// chunks, each assigned to `REDUNDANCY` distinct connection indices with no
+/// connection reused across chunks, so no two threads ever share a
+/// socket. Chunks that would be empty (when `byte_count` is smaller than the
+/// chunk count) are dropped; connections not assigned to any returned chunk
+/// sit idle this step.
+pub fn assign_chunks(connection_count: usize, byte_count: usize) -> Vec<ChunkAssignment> {
+    if connection_count < REDUNDANCY || byte_count == 0 {
+        return Vec::new();
+    }
+
+    let chunk_count = connection_count / REDUNDANCY;
+    let chunk_size = (byte_count + chunk_count - 1) / chunk_count;
+
+    (0..chunk_count)
+        .map(|chunk_index| {
+            let start = (chunk_index * chunk_size).min(byte_count);
+            let end = (start + chunk_size).min(byte_count);
+
+            let owners = (0..REDUNDANCY)
+                .map(|offset| chunk_index * REDUNDANCY + offset)
+                .collect();
+
+            ChunkAssignment { range: start..end, owners }
+        })
+        .filter(|assignment| !assignment.range.is_empty())
+        .collect()
+}
+
+/// A miner only ever receives its own chunk (see `handle_connection`), so it
+/// cannot correctly evolve cells whose Rule 30 neighbourhood reaches outside
+/// that chunk. Spot-checks must stay clear of this many bits on either edge
+/// of a chunk, or they'd penalize honest miners for data they were never
+/// given.
+pub const CHUNK_BOUNDARY_MARGIN: usize = 2;
+
+/// Deterministically samples `sample_count` bit indices (capped at
+/// `bit_count`) within `[0, bit_count)`, seeded by `seed` so the same step re-checks the same
+/// cells if it needs to be audited after the fact.
+pub fn sample_cell_indices(seed: u64, bit_count: usize, sample_count: usize) -> Vec<usize> {
+    if bit_count == 0 {
+        return Vec::new();
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..sample_count.min(bit_count))
+        .map(|_| rng.gen_range(0..bit_count))
+        .collect()
+}
+
+/// Like `sample_cell_indices`, but confined to the interior of a chunk of
+/// `bit_count` bits, i.e. excluding `CHUNK_BOUNDARY_MARGIN` bits on either
+/// edge that a miner couldn't have evolved correctly on its own.
+pub fn sample_interior_cell_indices(seed: u64, bit_count: usize, sample_count: usize) -> Vec<usize> {
+    let margin = CHUNK_BOUNDARY_MARGIN;
+
+    if bit_count <= margin * 2 {
+        return Vec::new();
+    }
+
+    sample_cell_indices(seed, bit_count - margin * 2, sample_count)
+        .into_iter()
+        .map(|index| index + margin)
+        .collect()
+}
+
+/// The Rule 30 transition for a single cell, matching the bit-parallel form
+/// this crate already uses in `rule_30` (`a ^ ((a << 1) | (a << 2))`) and the
+/// boundary stitching in `normalize_pair`: bits are read most-significant-bit
+/// first within a byte, and since the left shift moves a *lower* numeric bit
+/// into a *higher* one, a cell's next value depends on itself and the two
+/// cells that follow it (the next row positions, not the previous ones).
+/// `row` is the pre-step row; neighbours past the end of the row are
+/// treated as `0`.
+pub fn expected_bit(row: &[u8], bit_index: usize) -> u8 {
+    get_bit(row, bit_index) ^ (get_bit(row, bit_index + 1) | get_bit(row, bit_index + 2))
+}
+
+/// Reads bit `bit_index` out of `row`, treating indices beyond the row as `0`.
+pub fn actual_bit(row: &[u8], bit_index: usize) -> u8 {
+    get_bit(row, bit_index)
+}
+
+fn get_bit(row: &[u8], bit_index: usize) -> u8 {
+    let byte_index = bit_index / 8;
+
+    match row.get(byte_index) {
+        // Most-significant-bit-first, matching `normalize_pair`'s convention.
+        Some(byte) => (byte >> (7 - bit_index % 8)) & 1,
+        None => 0,
+    }
+}
+
+/// Finds `hotkey`'s slot in `scores`/`hotkeys` and folds `match_fraction`
+/// (the fraction of spot-checked cells that matched the expected
+/// transition) into its EMA. Hotkeys no longer present (e.g. deregistered
+/// mid-step) are silently ignored.
+pub fn record(scores: &mut [u16], hotkeys: &[AccountId], hotkey: &AccountId, match_fraction: f64) {
+    let Some(index) = hotkeys.iter().position(|candidate| candidate == hotkey) else {
+        return;
+    };
+
+    let previous = scores[index] as f64 / u16::MAX as f64;
+    let updated = previous * (1.0 - EMA_ALPHA) + match_fraction.clamp(0.0, 1.0) * EMA_ALPHA;
+
+    scores[index] = (updated.clamp(0.0, 1.0) * u16::MAX as f64) as u16;
+}
+
+/// Shorthand for `record(.., 0.0)`, used when a miner couldn't be reached
+/// or its exchange failed.
+pub fn penalize(scores: &mut [u16], hotkeys: &[AccountId], hotkey: &AccountId) {
+    record(scores, hotkeys, hotkey, 0.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Independent reference implementation of the MSB-first, forward-looking
+    // Rule 30 step, so the test isn't just calling back into `expected_bit`.
+    fn reference_bit(row: &[u8], index: usize) -> u8 {
+        let read = |index: usize| -> u8 {
+            let byte = index / 8;
+            match row.get(byte) {
+                Some(byte) => (byte >> (7 - index % 8)) & 1,
+                None => 0,
+            }
+        };
+
+        read(index) ^ (read(index + 1) | read(index + 2))
+    }
+
+    #[test]
+    fn msb_first_boundary_bit_matches_reference() {
+        let row = [0b10110100u8, 0b01101001];
+
+        // Bit 7 is the last (least-significant) bit of byte 0; its Rule 30
+        // neighbours (bits 8, 9) live in byte 1, so this exercises the
+        // cross-byte seam specifically.
+        assert_eq!(expected_bit(&row, 7), reference_bit(&row, 7));
+        assert_eq!(expected_bit(&row, 7), 1);
+    }
+
+    #[test]
+    fn honest_multi_byte_row_scores_perfectly() {
+        let previous = [0b10110100u8, 0b01101001, 0b11110000, 0b00011110];
+        let bit_count = previous.len() * 8;
+
+        let mut honest_next = vec![0u8; previous.len()];
+        for i in 0..bit_count {
+            if reference_bit(&previous, i) == 1 {
+                honest_next[i / 8] |= 1 << (7 - i % 8);
+            }
+        }
+
+        let matches = (0..bit_count)
+            .filter(|&i| actual_bit(&honest_next, i) == expected_bit(&previous, i))
+            .count();
+
+        assert_eq!(matches, bit_count);
+    }
+}